@@ -0,0 +1,128 @@
+//! A lock-free Bloom Filter variant for concurrent use, modeled on the
+//! `cbloom` crate.
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::base::{bit_mask, word_count, word_index};
+use crate::hash::{BloomBuildHasher, GxBuildHasher};
+
+use super::{BloomHash, BloomHashCount, BloomHashCounter};
+
+/// A Bloom Filter backed by `Box<[AtomicU64]>`, so `add`/`contains` can run
+/// concurrently behind a shared `&self` without an external mutex.
+///
+/// Both operations use [`Ordering::Relaxed`]: the filter only ever gains
+/// bits, never loses them, so there is nothing for another thread to
+/// synchronise against beyond the atomicity of each word itself.
+pub struct AtomicBloomFilter<const N: usize, const S: i64 = 0, H: BloomBuildHasher = GxBuildHasher>
+where
+    BloomHashCounter<N>: BloomHashCount,
+{
+    bits: Box<[AtomicU64]>,
+    _hasher: PhantomData<H>,
+}
+
+impl<const N: usize, const S: i64, H> Default for AtomicBloomFilter<N, S, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
+{
+    /// Create a new Atomic Bloom Filter.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const S: i64, H> AtomicBloomFilter<N, S, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
+{
+    /// Create a new Atomic Bloom Filter.
+    pub fn new() -> Self {
+        let len = word_count(1 << (128 / N));
+        let bits = (0..len).map(|_| AtomicU64::new(0)).collect();
+
+        Self {
+            bits,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Add a hash to the Atomic Bloom Filter.
+    pub fn add_hash(&self, hash: &BloomHash<N, S, H>) {
+        hash.hashes.iter().for_each(|i| {
+            self.bits[word_index(*i)].fetch_or(bit_mask(*i), Ordering::Relaxed);
+        });
+    }
+
+    /// Add a value to the Atomic Bloom Filter.
+    pub fn add(&self, value: impl AsRef<[u8]>) {
+        let hash = BloomHash::<N, S, H>::from(value);
+
+        self.add_hash(&hash);
+    }
+
+    /// Check if a value is a member of the Atomic Bloom Filter.
+    ///
+    /// This can only return false positives, not false negatives.
+    pub fn contains(&self, value: &[u8]) -> bool {
+        let hash: BloomHash<N, 0, H> = From::<&[u8]>::from(value);
+
+        hash.hashes
+            .iter()
+            .all(|i| self.bits[word_index(*i)].load(Ordering::Relaxed) & bit_mask(*i) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    macro_rules! expand_n {
+        ($(($name:ident, $n:literal)),*$(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    let filter = AtomicBloomFilter::<$n>::new();
+
+                    filter.add(b"hello");
+                    filter.add(b"world");
+
+                    assert!(filter.contains(b"hello"));
+                    assert!(filter.contains(b"world"));
+
+                    assert!(!filter.contains(b"foo"));
+                    assert!(!filter.contains(b"bar"));
+                }
+            )*
+
+    }}
+
+    expand_n!((n4, 4), (n8, 8),);
+
+    #[test]
+    fn concurrent_inserts_are_visible_across_threads() {
+        let filter = Arc::new(AtomicBloomFilter::<8>::new());
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let filter = Arc::clone(&filter);
+
+                std::thread::spawn(move || {
+                    filter.add(format!("value-{i}").as_bytes());
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .for_each(|handle| handle.join().unwrap());
+
+        (0..8).for_each(|i| {
+            assert!(filter.contains(format!("value-{i}").as_bytes()));
+        });
+    }
+}
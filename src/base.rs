@@ -1,5 +1,9 @@
 //! The base implementation of the [`BloomFilter`] and [`BloomHash`] types.
 
+use std::marker::PhantomData;
+
+use crate::hash::{BloomBuildHasher, GxBuildHasher};
+
 pub struct BloomHashCounter<const N: usize> {}
 
 /// Marker trait for `const`s that can be used as a Bloom filter hash function.
@@ -11,40 +15,66 @@ impl BloomHashCount for BloomHashCounter<8> {}
 
 pub const SEED: u128 = 127;
 
-pub struct BloomHash<const N: usize, const S: i64 = 0>
+/// Number of bits packed into each storage word.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// The word a given bit index falls into.
+pub(crate) const fn word_index(bit: usize) -> usize {
+    bit / WORD_BITS
+}
+
+/// A mask selecting a given bit index within its word.
+pub(crate) const fn bit_mask(bit: usize) -> u64 {
+    1 << (bit % WORD_BITS)
+}
+
+/// Number of `u64` words needed to store `bits` bits.
+pub(crate) const fn word_count(bits: usize) -> usize {
+    bits.div_ceil(WORD_BITS)
+}
+
+pub struct BloomHash<const N: usize, const S: i64 = 0, H: BloomBuildHasher = GxBuildHasher>
 where
     BloomHashCounter<N>: BloomHashCount,
 {
     pub hashes: Box<[usize]>,
+    _hasher: PhantomData<H>,
 }
 
-impl<const N: usize, const S: i64, T> From<T> for BloomHash<N, S>
+impl<const N: usize, const S: i64, H, T> From<T> for BloomHash<N, S, H>
 where
     BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
     T: AsRef<[u8]>,
 {
     fn from(value: T) -> Self {
-        let hashed = gxhash::gxhash128(value.as_ref(), 0);
-        let mask = (1 << (128 / N)) - 1;
+        let modulus = 1 << (128 / N);
 
         Self {
-            hashes: (0..N)
-                .map(|i| (hashed >> (i * (128 / N)) & mask) as usize)
-                .collect::<Box<[_]>>(),
+            hashes: H::default().hash_indices(value.as_ref(), N, modulus),
+            _hasher: PhantomData,
         }
     }
 }
 
-pub struct BloomFilter<const N: usize, const S: i64 = 0>
+/// A Bloom Filter backed by a bit-packed `Box<[u64]>`, rather than one
+/// `bool` per slot.
+///
+/// The hash function is pluggable via `H`, defaulting to
+/// [`GxBuildHasher`](crate::hash::GxBuildHasher) so existing `BloomFilter<N, S>`
+/// usage is unchanged.
+pub struct BloomFilter<const N: usize, const S: i64 = 0, H: BloomBuildHasher = GxBuildHasher>
 where
     BloomHashCounter<N>: BloomHashCount,
 {
-    pub bits: Box<[bool]>,
+    pub bits: Box<[u64]>,
+    _hasher: PhantomData<H>,
 }
 
-impl<const N: usize, const S: i64> Default for BloomFilter<N, S>
+impl<const N: usize, const S: i64, H> Default for BloomFilter<N, S, H>
 where
     BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
 {
     /// Create a new Bloom Filter.
     fn default() -> Self {
@@ -52,27 +82,29 @@ where
     }
 }
 
-impl<const N: usize, const S: i64> BloomFilter<N, S>
+impl<const N: usize, const S: i64, H> BloomFilter<N, S, H>
 where
     BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
 {
     /// Create a new Bloom Filter.
     pub fn new() -> Self {
         Self {
-            bits: vec![false; 1 << (128 / N)].into_boxed_slice(),
+            bits: vec![0; word_count(1 << (128 / N))].into_boxed_slice(),
+            _hasher: PhantomData,
         }
     }
 
     /// Add a hash to the Bloom Filter.
-    pub fn add_hash(&mut self, hash: &BloomHash<N, S>) {
+    pub fn add_hash(&mut self, hash: &BloomHash<N, S, H>) {
         hash.hashes.iter().for_each(|i| {
-            self.bits[*i] = true;
+            self.bits[word_index(*i)] |= bit_mask(*i);
         });
     }
 
     /// Add a value to the Bloom Filter.
     pub fn add(&mut self, value: impl AsRef<[u8]>) {
-        let hash = BloomHash::<N, S>::from(value);
+        let hash = BloomHash::<N, S, H>::from(value);
 
         self.add_hash(&hash);
     }
@@ -81,9 +113,84 @@ where
     ///
     /// This can only return false positives, not false negatives.
     pub fn contains(&self, value: &[u8]) -> bool {
-        let hash: BloomHash<N> = From::<&[u8]>::from(value);
+        let hash: BloomHash<N, 0, H> = From::<&[u8]>::from(value);
+
+        hash.hashes
+            .iter()
+            .all(|i| self.bits[word_index(*i)] & bit_mask(*i) != 0)
+    }
+
+    /// Estimate the false-positive rate of this filter if it holds
+    /// `n_elements` elements, using the standard formula
+    /// `p = (1 - (1 - 1/M)^{k*n})^k`, where `M` is the number of bit slots
+    /// and `k = N` is the hash count.
+    pub fn estimated_false_positive_rate(&self, n_elements: usize) -> f64 {
+        let m = (self.bits.len() * WORD_BITS) as f64;
+        let k = N as f64;
+        let n = n_elements as f64;
+
+        (1.0 - (1.0 - 1.0 / m).powf(k * n)).powf(k)
+    }
+
+    /// Combine with `other` via slot-wise OR, representing the union of
+    /// both filters' elements.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            bits: self
+                .bits
+                .iter()
+                .zip(other.bits.iter())
+                .map(|(a, b)| a | b)
+                .collect(),
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Combine with `other` via slot-wise AND, an approximate intersection
+    /// of both filters' elements.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            bits: self
+                .bits
+                .iter()
+                .zip(other.bits.iter())
+                .map(|(a, b)| a & b)
+                .collect(),
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Merge `other` into `self` in place via slot-wise OR, representing
+    /// the union of both filters' elements.
+    pub fn merge(&mut self, other: &Self) {
+        self.bits
+            .iter_mut()
+            .zip(other.bits.iter())
+            .for_each(|(a, b)| *a |= b);
+    }
+}
+
+impl<const N: usize, const S: i64, H> std::ops::BitOr for &BloomFilter<N, S, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
+{
+    type Output = BloomFilter<N, S, H>;
 
-        hash.hashes.iter().all(|i| self.bits[*i])
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl<const N: usize, const S: i64, H> std::ops::BitAnd for &BloomFilter<N, S, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    H: BloomBuildHasher,
+{
+    type Output = BloomFilter<N, S, H>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersect(rhs)
     }
 }
 
@@ -116,4 +223,92 @@ mod tests {
         (n4, 4),
         (n8, 8),
     );
+
+    #[test]
+    fn double_hash_build_hasher_roundtrips() {
+        use crate::hash::DoubleHashBuildHasher;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        // `BuildHasherDefault` (unlike `RandomState`) is deterministic across
+        // calls, which `DoubleHashBuildHasher` relies on to hash the same
+        // value to the same slots on every `add`/`contains`.
+        type SipFilter =
+            BloomFilter<4, 0, DoubleHashBuildHasher<BuildHasherDefault<DefaultHasher>>>;
+
+        let mut filter = SipFilter::new();
+
+        filter.add(b"hello");
+        filter.add(b"world");
+
+        assert!(filter.contains(b"hello"));
+        assert!(filter.contains(b"world"));
+
+        assert!(!filter.contains(b"foo"));
+        assert!(!filter.contains(b"bar"));
+    }
+
+    #[test]
+    fn estimated_false_positive_rate_increases_with_more_elements() {
+        let filter = BloomFilter::<8>::new();
+
+        let empty = filter.estimated_false_positive_rate(0);
+        let light = filter.estimated_false_positive_rate(1_000);
+        let heavy = filter.estimated_false_positive_rate(1_000_000);
+
+        assert_eq!(empty, 0.0);
+        assert!(light < heavy);
+        assert!(heavy <= 1.0);
+    }
+
+    #[test]
+    fn union_combines_both_filters_elements() {
+        let mut a = BloomFilter::<8>::new();
+        a.add(b"hello");
+
+        let mut b = BloomFilter::<8>::new();
+        b.add(b"world");
+
+        let union = a.union(&b);
+
+        assert!(union.contains(b"hello"));
+        assert!(union.contains(b"world"));
+        assert!(!union.contains(b"foo"));
+
+        // `BitOr` on `&BloomFilter` is equivalent to `union`.
+        let union_via_operator = &a | &b;
+        assert!(union_via_operator.contains(b"hello"));
+        assert!(union_via_operator.contains(b"world"));
+    }
+
+    #[test]
+    fn intersect_keeps_only_shared_bits() {
+        let mut a = BloomFilter::<8>::new();
+        a.add(b"hello");
+        a.add(b"world");
+
+        let mut b = BloomFilter::<8>::new();
+        b.add(b"world");
+
+        let intersection = a.intersect(&b);
+        assert!(intersection.contains(b"world"));
+
+        // `BitAnd` on `&BloomFilter` is equivalent to `intersect`.
+        let intersection_via_operator = &a & &b;
+        assert!(intersection_via_operator.contains(b"world"));
+    }
+
+    #[test]
+    fn merge_folds_other_into_self_in_place() {
+        let mut a = BloomFilter::<8>::new();
+        a.add(b"hello");
+
+        let mut b = BloomFilter::<8>::new();
+        b.add(b"world");
+
+        a.merge(&b);
+
+        assert!(a.contains(b"hello"));
+        assert!(a.contains(b"world"));
+    }
 }
@@ -0,0 +1,300 @@
+//! A counting variant of [`BloomFilter`] that supports per-element removal.
+
+use std::marker::PhantomData;
+
+use crate::hash::{BloomBuildHasher, GxBuildHasher};
+
+use super::{BloomHash, BloomHashCount, BloomHashCounter};
+
+/// A saturating counter slot usable by [`CountingBloomFilter`].
+///
+/// Implemented for the standard unsigned integer types, so the counter
+/// width can be tuned to the expected rate of hash collisions on a slot,
+/// e.g. `u8` for most workloads, `u16`/`u32` for heavier reuse of the same
+/// slots.
+pub trait SaturatingCounter: Copy + Default + Ord {
+    /// Increment the counter, saturating at the type's maximum value.
+    fn saturating_inc(&mut self);
+
+    /// Decrement the counter, saturating at zero.
+    fn saturating_dec(&mut self);
+
+    /// Whether the counter is at zero.
+    fn is_zero(&self) -> bool;
+
+    /// Add `other` into `self`, saturating at the type's maximum value.
+    ///
+    /// Used to fold two filters' slots together, e.g. for
+    /// [`CountingBloomFilter::union`].
+    fn saturating_merge_add(&mut self, other: Self);
+}
+
+macro_rules! impl_saturating_counter {
+    ($($t:ty),*$(,)?) => {
+        $(
+            impl SaturatingCounter for $t {
+                fn saturating_inc(&mut self) {
+                    *self = self.saturating_add(1);
+                }
+
+                fn saturating_dec(&mut self) {
+                    *self = self.saturating_sub(1);
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == 0
+                }
+
+                fn saturating_merge_add(&mut self, other: Self) {
+                    *self = self.saturating_add(other);
+                }
+            }
+        )*
+    };
+}
+
+impl_saturating_counter!(u8, u16, u32, u64);
+
+/// A Bloom Filter with per-slot saturating counters instead of booleans.
+///
+/// Unlike [`BloomFilter`], elements can be removed with
+/// [`remove`](Self::remove) without discarding the whole filter, at the
+/// cost of `C`'s width per slot rather than a single bit. The hash
+/// function is pluggable via `H`, defaulting to
+/// [`GxBuildHasher`](crate::hash::GxBuildHasher) just like [`BloomFilter`].
+pub struct CountingBloomFilter<
+    const N: usize,
+    const S: i64 = 0,
+    C = u8,
+    H: BloomBuildHasher = GxBuildHasher,
+> where
+    BloomHashCounter<N>: BloomHashCount,
+    C: SaturatingCounter,
+{
+    pub counters: Box<[C]>,
+    _hasher: PhantomData<H>,
+}
+
+impl<const N: usize, const S: i64, C, H> Default for CountingBloomFilter<N, S, C, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    C: SaturatingCounter,
+    H: BloomBuildHasher,
+{
+    /// Create a new Counting Bloom Filter.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const S: i64, C, H> CountingBloomFilter<N, S, C, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    C: SaturatingCounter,
+    H: BloomBuildHasher,
+{
+    /// Create a new Counting Bloom Filter.
+    pub fn new() -> Self {
+        Self {
+            counters: vec![C::default(); 1 << (128 / N)].into_boxed_slice(),
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Add a hash to the Counting Bloom Filter.
+    pub fn add_hash(&mut self, hash: &BloomHash<N, S, H>) {
+        hash.hashes.iter().for_each(|i| {
+            self.counters[*i].saturating_inc();
+        });
+    }
+
+    /// Add a value to the Counting Bloom Filter.
+    pub fn add(&mut self, value: impl AsRef<[u8]>) {
+        let hash = BloomHash::<N, S, H>::from(value);
+
+        self.add_hash(&hash);
+    }
+
+    /// Remove a value from the Counting Bloom Filter.
+    ///
+    /// Decrements saturate at zero, so removing a value that was never
+    /// added (e.g. one `contains` reported as a false positive) is safe: it
+    /// will not underflow, though it may cause `contains` to false-negative
+    /// on other, still-present elements sharing its slots afterwards.
+    pub fn remove(&mut self, value: &[u8]) {
+        let hash: BloomHash<N, S, H> = From::<&[u8]>::from(value);
+
+        hash.hashes.iter().for_each(|i| {
+            self.counters[*i].saturating_dec();
+        });
+    }
+
+    /// Check if a value is a member of the Counting Bloom Filter.
+    ///
+    /// This returns false negatives only after [`remove`](Self::remove) was
+    /// called for a value more times than it was added, or on a counter
+    /// that had saturated; otherwise it can only return false positives.
+    pub fn contains(&self, value: &[u8]) -> bool {
+        let hash: BloomHash<N, 0, H> = From::<&[u8]>::from(value);
+
+        hash.hashes.iter().all(|i| !self.counters[*i].is_zero())
+    }
+
+    /// Combine with `other` via slot-wise saturating addition, representing
+    /// the union of both filters' elements.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = Self {
+            counters: self.counters.clone(),
+            _hasher: PhantomData,
+        };
+
+        result.merge(other);
+        result
+    }
+
+    /// Combine with `other` via slot-wise minimum, an approximate
+    /// intersection of both filters' elements.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            counters: self
+                .counters
+                .iter()
+                .zip(other.counters.iter())
+                .map(|(a, b)| (*a).min(*b))
+                .collect(),
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Merge `other` into `self` in place via slot-wise saturating
+    /// addition, representing the union of both filters' elements.
+    pub fn merge(&mut self, other: &Self) {
+        self.counters
+            .iter_mut()
+            .zip(other.counters.iter())
+            .for_each(|(a, b)| a.saturating_merge_add(*b));
+    }
+}
+
+impl<const N: usize, const S: i64, C, H> std::ops::BitOr for &CountingBloomFilter<N, S, C, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    C: SaturatingCounter,
+    H: BloomBuildHasher,
+{
+    type Output = CountingBloomFilter<N, S, C, H>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl<const N: usize, const S: i64, C, H> std::ops::BitAnd for &CountingBloomFilter<N, S, C, H>
+where
+    BloomHashCounter<N>: BloomHashCount,
+    C: SaturatingCounter,
+    H: BloomBuildHasher,
+{
+    type Output = CountingBloomFilter<N, S, C, H>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersect(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! expand_n {
+        ($(($name:ident, $n:literal)),*$(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    let mut filter = CountingBloomFilter::<$n>::new();
+
+                    filter.add(b"hello");
+                    filter.add(b"world");
+
+                    assert!(filter.contains(b"hello"));
+                    assert!(filter.contains(b"world"));
+
+                    assert!(!filter.contains(b"foo"));
+                    assert!(!filter.contains(b"bar"));
+                }
+            )*
+
+    }}
+
+    expand_n!((n4, 4), (n8, 8),);
+
+    #[test]
+    fn remove_deletes_an_element() {
+        let mut filter = CountingBloomFilter::<8>::new();
+
+        filter.add(b"hello");
+        filter.add(b"world");
+        assert!(filter.contains(b"hello"));
+
+        filter.remove(b"hello");
+        assert!(!filter.contains(b"hello"));
+        assert!(filter.contains(b"world"));
+    }
+
+    #[test]
+    fn remove_saturates_at_zero() {
+        let mut filter = CountingBloomFilter::<8>::new();
+
+        filter.add(b"hello");
+
+        // Removing the same value twice should not underflow the counters.
+        filter.remove(b"hello");
+        filter.remove(b"hello");
+
+        assert!(!filter.contains(b"hello"));
+    }
+
+    #[test]
+    fn union_combines_both_filters_elements() {
+        let mut a = CountingBloomFilter::<8>::new();
+        a.add(b"hello");
+
+        let mut b = CountingBloomFilter::<8>::new();
+        b.add(b"world");
+
+        let union = a.union(&b);
+
+        assert!(union.contains(b"hello"));
+        assert!(union.contains(b"world"));
+        assert!(!union.contains(b"foo"));
+    }
+
+    #[test]
+    fn intersect_keeps_only_shared_elements() {
+        let mut a = CountingBloomFilter::<8>::new();
+        a.add(b"hello");
+        a.add(b"world");
+
+        let mut b = CountingBloomFilter::<8>::new();
+        b.add(b"world");
+
+        let intersection = a.intersect(&b);
+
+        assert!(intersection.contains(b"world"));
+        assert!(!intersection.contains(b"hello"));
+    }
+
+    #[test]
+    fn merge_folds_other_into_self_in_place() {
+        let mut a = CountingBloomFilter::<8>::new();
+        a.add(b"hello");
+
+        let mut b = CountingBloomFilter::<8>::new();
+        b.add(b"world");
+
+        a.merge(&b);
+
+        assert!(a.contains(b"hello"));
+        assert!(a.contains(b"world"));
+    }
+}
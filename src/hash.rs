@@ -0,0 +1,144 @@
+//! Pluggable hash functions for [`BloomHash`](crate::BloomHash).
+
+/// Produces the slot indices used by a [`BloomHash`](crate::BloomHash).
+///
+/// Implementations decide how best to turn their own hash into `n`
+/// roughly-independent indices, each less than `modulus`: a hasher with a
+/// wide native digest (like the default [`GxBuildHasher`]) can slice a
+/// single 128-bit hash, while one built on a standard 64-bit
+/// [`std::hash::Hasher`] should fall back to double hashing (see
+/// [`DoubleHashBuildHasher`]).
+pub trait BloomBuildHasher: Default {
+    /// Hash `value` into `n` slot indices, each less than `modulus`.
+    ///
+    /// `modulus` is always a power of two.
+    fn hash_indices(&self, value: &[u8], n: usize, modulus: usize) -> Box<[usize]>;
+}
+
+/// The default hasher, producing a single 128-bit [`gxhash`] digest and
+/// slicing it into `n` slot indices. This matches the crate's original
+/// behaviour.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GxBuildHasher;
+
+impl BloomBuildHasher for GxBuildHasher {
+    fn hash_indices(&self, value: &[u8], n: usize, modulus: usize) -> Box<[usize]> {
+        // `0`, not `SEED`, to match the crate's original behaviour.
+        let hashed = gxhash::gxhash128(value, 0);
+        let bits = 128 / n;
+        let mask = (1u128 << bits) - 1;
+        let index_mask = modulus - 1;
+
+        (0..n)
+            .map(|i| (((hashed >> (i * bits)) & mask) as usize) & index_mask)
+            .collect()
+    }
+}
+
+/// Double hashing (Kirsch-Mitzenmacher) over any standard
+/// [`std::hash::BuildHasher`] (SipHash, FNV, xxHash, ...), deriving the `n`
+/// slot indices as `g_i = (h1 + i*h2) mod modulus` from two 64-bit digests
+/// of `value`.
+///
+/// This lets a hasher that only exposes the standard 64-bit
+/// [`std::hash::Hasher::finish`] still drive a [`BloomHash`](crate::BloomHash)
+/// with `N` independent-looking slots.
+///
+/// `S` is re-built from its [`Default`] impl on every hash, so it must
+/// produce identical hashers across calls for the same value, e.g.
+/// [`std::hash::BuildHasherDefault`]. A process-randomized hasher like
+/// [`std::hash::RandomState`] will make `add`ed values fail `contains`,
+/// since each call gets different keys.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DoubleHashBuildHasher<S>(std::marker::PhantomData<S>)
+where
+    S: std::hash::BuildHasher + Default;
+
+impl<S> BloomBuildHasher for DoubleHashBuildHasher<S>
+where
+    S: std::hash::BuildHasher + Default,
+{
+    fn hash_indices(&self, value: &[u8], n: usize, modulus: usize) -> Box<[usize]> {
+        use std::hash::Hasher;
+
+        let build = S::default();
+
+        let mut h1 = build.build_hasher();
+        h1.write(value);
+        let h1 = h1.finish();
+
+        let mut h2 = build.build_hasher();
+        h2.write(value);
+        h2.write(&[0xff]);
+        let h2 = h2.finish();
+
+        let index_mask = (modulus - 1) as u64;
+
+        (0..n as u64)
+            .map(|i| (h1.wrapping_add(i.wrapping_mul(h2)) & index_mask) as usize)
+            .collect()
+    }
+}
+
+/// A hasher for already-hashed (or otherwise pre-uniform) keys: treats the
+/// first 16 bytes of `value` (zero-padded if shorter) as an `(h1, h2)` pair
+/// instead of hashing it again, folds `value`'s length into `h2`, then
+/// derives the `n` slot indices via double hashing.
+///
+/// Useful for domain-specific keys, e.g. integer ids that are already
+/// uniformly distributed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoHasher;
+
+impl BloomBuildHasher for NoHasher {
+    fn hash_indices(&self, value: &[u8], n: usize, modulus: usize) -> Box<[usize]> {
+        let mut buf = [0u8; 16];
+        let len = value.len().min(16);
+        buf[..len].copy_from_slice(&value[..len]);
+
+        let h1 = u64::from_le_bytes(buf[0..8].try_into().unwrap());
+        let mut h2 = u64::from_le_bytes(buf[8..16].try_into().unwrap());
+
+        // `value` shorter than 16 bytes (e.g. a `u64` id passed as raw
+        // bytes, exactly the use case documented above) leaves `h2`
+        // zero-padded, which would otherwise collapse every slot to `h1`.
+        // Fold the length in so short keys still vary `h2`.
+        h2 ^= (value.len() as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+
+        let index_mask = (modulus - 1) as u64;
+
+        (0..n as u64)
+            .map(|i| (h1.wrapping_add(i.wrapping_mul(h2)) & index_mask) as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gx_build_hasher_indices_are_in_range() {
+        let indices = GxBuildHasher.hash_indices(b"hello", 4, 1 << 32);
+
+        assert_eq!(indices.len(), 4);
+        assert!(indices.iter().all(|i| *i < 1 << 32));
+    }
+
+    #[test]
+    fn no_hasher_indices_are_in_range_and_deterministic() {
+        let a = NoHasher.hash_indices(b"some-pre-hashed-key", 4, 1 << 16);
+        let b = NoHasher.hash_indices(b"some-pre-hashed-key", 4, 1 << 16);
+
+        assert_eq!(a, b);
+        assert!(a.iter().all(|i| *i < 1 << 16));
+    }
+
+    #[test]
+    fn no_hasher_does_not_collapse_keys_of_eight_bytes_or_fewer() {
+        let indices = NoHasher.hash_indices(&42u64.to_le_bytes(), 4, 1 << 16);
+
+        assert_eq!(indices.len(), 4);
+        assert!(indices.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+}
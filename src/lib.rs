@@ -0,0 +1,21 @@
+//! A Bloom Filter implementation with configurable hash count, backed by
+//! [`gxhash`] by default.
+
+mod atomic;
+mod base;
+mod counting;
+mod hash;
+mod rolling;
+mod shift;
+mod sizing;
+
+pub use atomic::AtomicBloomFilter;
+pub use base::{BloomFilter, BloomHash, BloomHashCount, BloomHashCounter, SEED};
+pub use counting::{CountingBloomFilter, SaturatingCounter};
+pub use hash::{BloomBuildHasher, DoubleHashBuildHasher, GxBuildHasher, NoHasher};
+pub use rolling::RollingBloomFilter;
+pub use shift::{
+    ShiftByDuration, ShiftByInsertions, ShiftCondition, DEFAULT_SHIFT_DURATION,
+    DEFAULT_SHIFT_INSERTIONS,
+};
+pub use sizing::recommended_parameters;
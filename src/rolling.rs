@@ -1,55 +1,214 @@
 //! Rolling window of Bloom Filters implementation.
 //!
 
-use crate::BloomHash;
-
-use super::{BloomFilter, BloomHashCount, BloomHashCounter, ShiftCondition};
-
-/// A rolling window of 2 Bloom Filters.
-pub struct RollingBloomFilter<const N: usize, T, const S: i64 = 0>
-where
-    BloomFilter<N, S>: Default,
+use std::marker::PhantomData;
+
+use crate::base::{bit_mask, word_index};
+use crate::hash::{BloomBuildHasher, GxBuildHasher};
+use crate::{BloomFilter, BloomHash};
+
+use super::{BloomHashCount, BloomHashCounter, ShiftCondition};
+
+/// Generation id marking a slot that has never been written to.
+///
+/// Stored generation ids are the logical generation plus one (see
+/// [`RollingBloomFilter::stored_generation`]), freeing up `0` for this
+/// sentinel so `generations` can be zero-filled on construction instead of
+/// written byte-by-byte, which is significantly faster for large tables.
+const EMPTY_GENERATION: u8 = 0;
+
+/// Default number of generation bits, giving `(1 << 2) - 1 = 3` generation
+/// buckets, `2` of which are live at any moment (see
+/// [`RollingBloomFilter::generation_count`]).
+pub const DEFAULT_GENERATION_BITS: u32 = 2;
+
+/// Number of bits packed into each `u64` word of the bit-packed generation
+/// table.
+const GENERATION_WORD_BITS: usize = u64::BITS as usize;
+
+/// A generation-tagged rolling window of Bloom Filter membership.
+///
+/// Rather than swapping between two whole [`BloomFilter`](crate::BloomFilter)
+/// instances, each slot stores a small generation id (`GENERATION_BITS`
+/// wide, giving `GENERATION_COUNT = (1 << GENERATION_BITS) - 1` generation
+/// buckets) packed into a `Box<[u64]>`, the same way [`BloomFilter`]
+/// bit-packs its single-bit slots. `add` stamps the `N` hash slots for a
+/// value with the current generation; `contains` treats a slot as live
+/// unless it still holds the generation id that is due to be recycled
+/// next, so elements expire smoothly across `GENERATION_COUNT - 1` windows
+/// instead of all at once (the bucket currently being recycled is never
+/// live — see [`generation_count`](Self::generation_count)).
+pub struct RollingBloomFilter<
+    const N: usize,
+    T,
+    const S: i64 = 0,
+    const GENERATION_BITS: u32 = DEFAULT_GENERATION_BITS,
+    H: BloomBuildHasher = GxBuildHasher,
+> where
     BloomHashCounter<N>: BloomHashCount,
     T: ShiftCondition,
 {
-    filters: [BloomFilter<N, S>; 2],
+    generations: Box<[u64]>,
+    current_generation: u8,
+    generation_insertion_counts: Box<[usize]>,
+    inserted_since_last_roll: usize,
     shift_condition: T,
+    _hasher: PhantomData<H>,
 }
 
-impl<const N: usize, T, const S: i64> Default for RollingBloomFilter<N, T, S>
+impl<const N: usize, T, const S: i64, const GENERATION_BITS: u32, H> Default
+    for RollingBloomFilter<N, T, S, GENERATION_BITS, H>
 where
-    BloomFilter<N, S>: Default,
     BloomHashCounter<N>: BloomHashCount,
     T: Default + ShiftCondition,
+    H: BloomBuildHasher,
 {
     fn default() -> Self {
-        Self {
-            filters: [BloomFilter::default(), BloomFilter::default()],
-            shift_condition: T::default(),
-        }
+        Self::new(T::default())
     }
 }
 
-impl<const N: usize, T, const S: i64> RollingBloomFilter<N, T, S>
+impl<const N: usize, T, const S: i64, const GENERATION_BITS: u32, H>
+    RollingBloomFilter<N, T, S, GENERATION_BITS, H>
 where
-    BloomFilter<N, S>: Default,
     BloomHashCounter<N>: BloomHashCount,
     T: ShiftCondition,
+    H: BloomBuildHasher,
 {
-    /// Create a new rolling window of Bloom Filters with the provided shift condition.
+    /// Number of generation buckets in the rotation, `(1 << GENERATION_BITS)
+    /// - 1`.
+    ///
+    /// A write only survives `generation_count() - 1` of these buckets, not
+    /// all of them: one id is always reserved as "the one currently being
+    /// recycled" (see [`recycling_generation`](Self::recycling_generation)),
+    /// and a write made while its own bucket is current is never itself the
+    /// one being recycled, so [`contains`](Self::contains) only reports it
+    /// as expired once every other bucket has cycled through.
+    pub fn generation_count() -> usize {
+        ((1u32 << GENERATION_BITS) - 1) as usize
+    }
+
+    /// Number of hash slots in the generation table, matching the modulus
+    /// [`BloomHash`] hashes into for the same `N`.
+    fn slot_count() -> usize {
+        1 << (128 / N)
+    }
+
+    /// The `(word, bit offset within that word)` a slot's `GENERATION_BITS`
+    /// -wide field starts at within the flat `generations` bit array.
+    fn field_location(index: usize) -> (usize, usize) {
+        let bit_offset = index * GENERATION_BITS as usize;
+
+        (
+            bit_offset / GENERATION_WORD_BITS,
+            bit_offset % GENERATION_WORD_BITS,
+        )
+    }
+
+    /// Read the stored generation id out of slot `index`'s bit-packed field.
+    fn get_generation(&self, index: usize) -> u8 {
+        let (word, bit) = Self::field_location(index);
+        let mask = (1u64 << GENERATION_BITS) - 1;
+
+        let value = if bit + GENERATION_BITS as usize <= GENERATION_WORD_BITS {
+            (self.generations[word] >> bit) & mask
+        } else {
+            let low_bits = GENERATION_WORD_BITS - bit;
+            let low = self.generations[word] >> bit;
+            let high = self.generations[word + 1] & (mask >> low_bits);
+
+            low | (high << low_bits)
+        };
+
+        value as u8
+    }
+
+    /// Write `value` into slot `index`'s bit-packed field.
+    fn set_generation(&mut self, index: usize, value: u8) {
+        let (word, bit) = Self::field_location(index);
+        let mask = (1u64 << GENERATION_BITS) - 1;
+        let value = value as u64 & mask;
+
+        if bit + GENERATION_BITS as usize <= GENERATION_WORD_BITS {
+            self.generations[word] = (self.generations[word] & !(mask << bit)) | (value << bit);
+        } else {
+            let low_bits = GENERATION_WORD_BITS - bit;
+
+            self.generations[word] = (self.generations[word] & !(mask << bit)) | (value << bit);
+
+            let high_mask = mask >> low_bits;
+            self.generations[word + 1] =
+                (self.generations[word + 1] & !high_mask) | (value >> low_bits);
+        }
+    }
+
+    /// Number of elements added since the last roll, regardless of which
+    /// generation they landed in.
+    pub fn inserted_since_last_roll(&self) -> usize {
+        self.inserted_since_last_roll
+    }
+
+    /// The stored form of a logical generation id, offset by one so that
+    /// `0` is free to mean [`EMPTY_GENERATION`].
+    fn stored_generation(generation: u8) -> u8 {
+        generation + 1
+    }
+
+    /// The generation id that is due to be recycled next.
+    ///
+    /// A hash slot still holding this id is stale data from a full cycle
+    /// ago and is therefore treated as expired, even before it is
+    /// physically overwritten by a new insertion.
+    fn recycling_generation(&self) -> u8 {
+        (self.current_generation + 1) % Self::generation_count() as u8
+    }
+
+    /// Create a new rolling window of Bloom Filters with the provided shift
+    /// condition.
+    ///
+    /// `GENERATION_BITS` must be between 2 and 8 inclusive: below 2 there is
+    /// no id left over for "the one currently being recycled" once the just
+    /// -written generation is excluded (`GENERATION_BITS = 0` divides by zero
+    /// in [`recycling_generation`](Self::recycling_generation);
+    /// `GENERATION_BITS = 1` makes every write immediately expire itself),
+    /// and above 8 `generation_count` no longer fits in the `u8`
+    /// `current_generation` counter this type stamps and tracks
+    /// generations with, silently truncating the real expiry window.
     pub fn new(shift_condition: T) -> Self {
+        const {
+            assert!(
+                GENERATION_BITS >= 2 && GENERATION_BITS <= 8,
+                "GENERATION_BITS must be between 2 and 8 inclusive"
+            );
+        }
+
+        let word_count =
+            (Self::slot_count() * GENERATION_BITS as usize).div_ceil(GENERATION_WORD_BITS);
+
         Self {
-            filters: [BloomFilter::default(), BloomFilter::default()],
+            // Zero-filled, so this allocates via the OS's zero page instead
+            // of writing every word individually; `EMPTY_GENERATION` is `0`
+            // for exactly this reason.
+            generations: vec![0u64; word_count].into_boxed_slice(),
+            current_generation: 0,
+            generation_insertion_counts: vec![0; Self::generation_count()].into_boxed_slice(),
+            inserted_since_last_roll: 0,
             shift_condition,
+            _hasher: PhantomData,
         }
     }
 
     /// Add an element to the rolling window of Bloom Filters.
     pub fn add(&mut self, value: &[u8]) {
-        let hash: BloomHash<N, S> = value.into();
+        let hash: BloomHash<N, S, H> = value.into();
+        let stored_generation = Self::stored_generation(self.current_generation);
 
-        self.filters[0].add_hash(&hash);
-        self.filters[1].add_hash(&hash);
+        hash.hashes.iter().for_each(|i| {
+            self.set_generation(*i, stored_generation);
+        });
+
+        self.generation_insertion_counts[self.current_generation as usize] += 1;
+        self.inserted_since_last_roll += 1;
 
         // Shift the filter if the condition is met.
         if self.shift_condition.should_shift_after_increment() {
@@ -57,21 +216,66 @@ where
         }
     }
 
-    /// Shift the rolling window of Bloom Filters.
+    /// Shift the rolling window of Bloom Filters, advancing the current
+    /// generation so that the oldest one starts expiring.
     pub fn shift(&mut self) {
         self.shift_condition.do_shift();
 
-        // Replace the oldest filter with a new one.
-        self.filters[0] = BloomFilter::default();
-
-        // Swap the filters so that the oldest filter is always the first one.
-        self.filters.swap(0, 1);
+        self.current_generation = (self.current_generation + 1) % Self::generation_count() as u8;
+        self.generation_insertion_counts[self.current_generation as usize] = 0;
+        self.inserted_since_last_roll = 0;
     }
 
-    /// Check if an element is a member of the rolling window of Bloom Filters.
+    /// Check if an element is a member of the rolling window of Bloom
+    /// Filters.
     pub fn contains(&self, value: &[u8]) -> bool {
-        // We only need to check the oldest filter.
-        self.filters[0].contains(value)
+        let hash: BloomHash<N, S, H> = value.into();
+        let recycling_generation = Self::stored_generation(self.recycling_generation());
+
+        hash.hashes.iter().all(|i| {
+            let generation = self.get_generation(*i);
+
+            generation != EMPTY_GENERATION && generation != recycling_generation
+        })
+    }
+
+    /// Estimate the false-positive rate across the live generations,
+    /// excluding the generation currently being recycled since its
+    /// elements are already treated as expired by [`contains`](Self::contains).
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let m = Self::slot_count() as f64;
+        let k = N as f64;
+        let recycling_generation = self.recycling_generation();
+
+        let n: usize = self
+            .generation_insertion_counts
+            .iter()
+            .enumerate()
+            .filter(|(generation, _)| *generation as u8 != recycling_generation)
+            .map(|(_, count)| count)
+            .sum();
+
+        (1.0 - (1.0 - 1.0 / m).powf(k * n as f64)).powf(k)
+    }
+
+    /// Fold the generation about to be recycled into `target` by setting
+    /// `target`'s bit for each of its slots (the same slot-wise OR as
+    /// [`BloomFilter::merge`]), so its elements survive past this window's
+    /// expiry in a long-term summary instead of being discarded outright.
+    ///
+    /// [`contains`](Self::contains) already treats this generation as
+    /// expired, but its slots are not physically overwritten until a future
+    /// [`add`](Self::add) reuses them for a newer generation, so this must be
+    /// called before that happens to capture its elements; after the next
+    /// [`shift`](Self::shift) a different generation is the recycling one.
+    pub fn fold_recycling_generation_into(&self, target: &mut BloomFilter<N, S, H>) {
+        let recycling_generation = Self::stored_generation(self.recycling_generation());
+
+        (0..Self::slot_count())
+            .filter(|i| self.get_generation(*i) == recycling_generation)
+            .for_each(|i| {
+                target.bits[word_index(i)] |= bit_mask(i);
+            });
     }
 }
 
@@ -108,8 +312,8 @@ mod tests {
         assert!(rolling_bloom_filter.contains("qux".as_bytes()));
         assert!(rolling_bloom_filter.contains("bar".as_bytes()));
 
-        // The oldest filter should have been shifted, resulting in the first
-        // three insertions being removed from the filter.
+        // The oldest generation should have been recycled, resulting in the
+        // first three insertions being removed from the filter.
         assert!(!rolling_bloom_filter.contains("foo".as_bytes()));
         assert!(!rolling_bloom_filter.contains("hello".as_bytes()));
         assert!(!rolling_bloom_filter.contains("world".as_bytes()));
@@ -149,9 +353,68 @@ mod tests {
         assert!(rolling_bloom_filter.contains("bar".as_bytes()));
 
         // `foo` won't exist because `foo` was added before the shift, and the
-        // oldest filter was shifted.
+        // oldest generation was recycled.
         assert!(!rolling_bloom_filter.contains("foo".as_bytes()));
         assert!(!rolling_bloom_filter.contains("hello".as_bytes()));
         assert!(!rolling_bloom_filter.contains("world".as_bytes()));
     }
+
+    #[test]
+    fn more_than_two_live_windows() {
+        // `GENERATION_BITS = 3` gives 7 generation buckets, 6 of which are
+        // live at any moment, letting an element survive many more shifts
+        // than the old 2-filter design ever allowed.
+        let shift_condition = ShiftByInsertions::new(1);
+        let mut rolling_bloom_filter: RollingBloomFilter<8, _, 0, 3> =
+            RollingBloomFilter::new(shift_condition);
+
+        rolling_bloom_filter.add("hello".as_bytes());
+        assert!(rolling_bloom_filter.contains("hello".as_bytes()));
+
+        for filler in ["a", "b", "c", "d"] {
+            rolling_bloom_filter.add(filler.as_bytes());
+            assert!(rolling_bloom_filter.contains("hello".as_bytes()));
+        }
+
+        // One more shift recycles generation 0, where `hello` still lives.
+        rolling_bloom_filter.add("e".as_bytes());
+        assert!(!rolling_bloom_filter.contains("hello".as_bytes()));
+    }
+
+    #[test]
+    fn estimated_false_positive_rate_is_zero_until_an_element_is_added() {
+        let shift_condition = ShiftByInsertions::new(1_000);
+        let mut rolling_bloom_filter = RollingBloomFilter::<8, _>::new(shift_condition);
+
+        assert_eq!(rolling_bloom_filter.estimated_false_positive_rate(), 0.0);
+
+        rolling_bloom_filter.add("hello".as_bytes());
+        assert!(rolling_bloom_filter.estimated_false_positive_rate() > 0.0);
+    }
+
+    #[test]
+    fn fold_recycling_generation_into_preserves_expiring_elements() {
+        let shift_condition = ShiftByInsertions::new(1);
+        let mut rolling_bloom_filter: RollingBloomFilter<8, _, 0, 3> =
+            RollingBloomFilter::new(shift_condition);
+
+        rolling_bloom_filter.add("hello".as_bytes());
+
+        // As in `more_than_two_live_windows`, this leaves `hello`'s
+        // generation as the one `contains` already treats as recycling,
+        // even though its raw slots haven't been overwritten yet.
+        for filler in ["a", "b", "c", "d", "e"] {
+            rolling_bloom_filter.add(filler.as_bytes());
+        }
+
+        assert!(!rolling_bloom_filter.contains("hello".as_bytes()));
+
+        // The physical slots are still intact, so folding now preserves
+        // `hello` in a long-term summary before a future add overwrites
+        // them with a newer generation.
+        let mut summary = BloomFilter::<8>::new();
+        rolling_bloom_filter.fold_recycling_generation_into(&mut summary);
+
+        assert!(summary.contains("hello".as_bytes()));
+    }
 }
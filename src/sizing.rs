@@ -0,0 +1,37 @@
+//! Helpers for choosing Bloom Filter parameters ahead of time.
+
+/// Recommend a bit count and hash count for a Bloom Filter expected to hold
+/// `n_elements` elements at a target false-positive rate `p`.
+///
+/// Uses the standard formulas `M = ceil(-n * ln(p) / (ln 2)^2)` for the bit
+/// count and `k = round((M / n) * ln 2)` for the hash count.
+pub fn recommended_parameters(p: f64, n_elements: usize) -> (usize, usize) {
+    let n = n_elements as f64;
+    let ln2 = std::f64::consts::LN_2;
+
+    let bit_count = (-n * p.ln() / (ln2 * ln2)).ceil() as usize;
+    let hash_count = ((bit_count as f64 / n) * ln2).round() as usize;
+
+    (bit_count, hash_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommends_more_bits_for_a_lower_false_positive_rate() {
+        let (loose_bits, _) = recommended_parameters(0.1, 1_000);
+        let (tight_bits, _) = recommended_parameters(0.001, 1_000);
+
+        assert!(tight_bits > loose_bits);
+    }
+
+    #[test]
+    fn recommends_a_sensible_hash_count() {
+        // Well-known rule of thumb: k ~= 7 for p = 0.01.
+        let (_, hash_count) = recommended_parameters(0.01, 10_000);
+
+        assert!((6..=8).contains(&hash_count));
+    }
+}